@@ -1,181 +1,693 @@
+mod auth;
+mod error;
+mod queue;
+mod telemetry;
+
 use crate::config;
 use crate::flops::measure_flops;
 use crate::memory_stats::get_memory_info;
 use crate::nexus_orchestrator::{
     GetProofTaskRequest, GetProofTaskResponse, NodeType, SubmitProofRequest, NodeTelemetry,
 };
+use auth::AuthManager;
+pub use error::{ApiError, OrchestratorError};
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
 use prost::Message;
+pub use queue::{ProofStatus, QueuedProof};
+use queue::ProofQueue;
+use rand::Rng;
 use reqwest::Client;
-use serde::Serialize;
-use std::fs::File;
-use std::io::{self, Write};
-use base64;
-
-/// Struct for serializing `SubmitProofRequest` to JSON
-#[derive(Serialize)]
-struct SubmitProofRequestJson {
-    node_id: String,
-    node_type: i32,
-    proof_hash: String,
-    proof: String, // Base64-encoded proof data
-    node_telemetry: Option<NodeTelemetryJson>,
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+pub use telemetry::TelemetryConfig;
+use telemetry::GeoLocator;
+
+/// Compression codec applied to request bodies at or above [`CompressionConfig::threshold_bytes`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CompressionCodec {
+    Deflate,
+    Gzip,
+    None,
+}
+
+/// Controls transparent compression of large request bodies (proof uploads).
+#[derive(Clone)]
+pub struct CompressionConfig {
+    pub codec: CompressionCodec,
+    /// Bodies smaller than this are sent uncompressed.
+    pub threshold_bytes: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            codec: CompressionCodec::Deflate,
+            threshold_bytes: 8 * 1024,
+        }
+    }
+}
+
+/// Exponential backoff parameters used when retrying requests against the orchestrator.
+#[derive(Clone)]
+pub struct BackoffConfig {
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Multiplier applied to the delay after each retry.
+    pub factor: f64,
+    /// Maximum number of retries for a single logical request, counted across
+    /// all hosts.
+    pub max_retries: u32,
+    /// Upper bound on the total time spent retrying a single logical request.
+    pub max_elapsed: Duration,
+    /// Number of consecutive failures on a host before rotating to the next fallback.
+    pub failures_before_rotate: u32,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(500),
+            factor: 2.0,
+            max_retries: 5,
+            max_elapsed: Duration::from_secs(30),
+            failures_before_rotate: 2,
+        }
+    }
+}
+
+impl BackoffConfig {
+    /// Computes the delay before the given retry attempt (0-indexed), including jitter.
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exp = self.factor.powi(attempt as i32);
+        let millis = (self.base_delay.as_millis() as f64 * exp).min(u32::MAX as f64);
+        let jitter: f64 = rand::thread_rng().gen_range(0.5..1.5);
+        Duration::from_millis((millis * jitter) as u64)
+    }
+}
+
+/// Timeouts applied to the underlying `reqwest::Client`.
+#[derive(Clone)]
+pub struct TimeoutConfig {
+    /// Time allowed to establish the TCP/TLS connection.
+    pub connect_timeout: Duration,
+    /// Time allowed for the whole request, including large proof uploads.
+    pub request_timeout: Duration,
+    /// How long an idle pooled connection is kept before being closed.
+    pub pool_idle_timeout: Duration,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            // Proof submission payloads can be large, so default generously.
+            request_timeout: Duration::from_secs(120),
+            pool_idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+/// Builder for [`OrchestratorClient`].
+pub struct OrchestratorClientBuilder {
+    hosts: Vec<String>,
+    backoff: BackoffConfig,
+    timeouts: TimeoutConfig,
+    compression: CompressionConfig,
+    queue_path: PathBuf,
+    telemetry: TelemetryConfig,
 }
 
-/// Struct for serializing `NodeTelemetry`
-#[derive(Serialize)]
-struct NodeTelemetryJson {
-    flops_per_sec: Option<i32>,
-    memory_used: Option<i64>,
-    memory_capacity: Option<i64>,
-    location: Option<String>,
+impl OrchestratorClientBuilder {
+    pub fn new(environment: config::Environment) -> Self {
+        Self {
+            hosts: vec![environment.orchestrator_url()],
+            backoff: BackoffConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            compression: CompressionConfig::default(),
+            queue_path: queue::default_queue_path(),
+            telemetry: TelemetryConfig::new(),
+        }
+    }
+
+    /// Adds an ordered list of fallback base URLs to try after the primary host.
+    pub fn with_fallback_hosts(mut self, fallback_hosts: Vec<String>) -> Self {
+        self.hosts.extend(fallback_hosts);
+        self
+    }
+
+    /// Overrides the maximum number of retries per logical request.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.backoff.max_retries = max_retries;
+        self
+    }
+
+    /// Overrides the backoff schedule used between retries.
+    pub fn backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Overrides the connect/request/pool-idle timeouts used by the HTTP client.
+    pub fn timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Overrides the codec and size threshold used to compress request bodies.
+    pub fn compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Overrides where the durable proof queue's `sled` tree is stored.
+    pub fn queue_path(mut self, queue_path: PathBuf) -> Self {
+        self.queue_path = queue_path;
+        self
+    }
+
+    /// Overrides geolocation and bandwidth-measurement behavior for submitted telemetry.
+    pub fn telemetry(mut self, telemetry: TelemetryConfig) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    pub fn build(self) -> OrchestratorClient {
+        let client = Client::builder()
+            .connect_timeout(self.timeouts.connect_timeout)
+            .timeout(self.timeouts.request_timeout)
+            .pool_idle_timeout(self.timeouts.pool_idle_timeout)
+            .build()
+            .expect("failed to build HTTP client");
+
+        let queue = ProofQueue::open(&self.queue_path);
+
+        OrchestratorClient {
+            client,
+            hosts: self.hosts,
+            current_host: AtomicUsize::new(0),
+            backoff: self.backoff,
+            auth: AuthManager::load(),
+            compression: self.compression,
+            queue: Arc::new(queue),
+            geo: GeoLocator::new(self.telemetry.location_override.clone()),
+            telemetry: self.telemetry,
+        }
+    }
 }
 
 pub struct OrchestratorClient {
     client: Client,
-    base_url: String,
+    hosts: Vec<String>,
+    current_host: AtomicUsize,
+    backoff: BackoffConfig,
+    auth: AuthManager,
+    compression: CompressionConfig,
+    queue: Arc<ProofQueue>,
+    geo: GeoLocator,
+    telemetry: TelemetryConfig,
 }
 
 impl OrchestratorClient {
     pub fn new(environment: config::Environment) -> Self {
-        Self {
-            client: Client::new(),
-            base_url: environment.orchestrator_url(),
+        OrchestratorClientBuilder::new(environment).build()
+    }
+
+    fn base_url(&self) -> &str {
+        // `current_host` is only ever advanced, never reset, so wrap defensively.
+        let idx = self.current_host.load(Ordering::Relaxed) % self.hosts.len();
+        &self.hosts[idx]
+    }
+
+    fn rotate_host(&self) {
+        if self.hosts.len() > 1 {
+            self.current_host.fetch_add(1, Ordering::Relaxed);
         }
     }
 
+    /// Compresses `bytes` with the configured codec if it's at or above the
+    /// threshold, returning the body to send and the `Content-Encoding` to
+    /// advertise. `bytes` is already a fully materialized buffer by the time
+    /// it gets here (it's `request_data.encode_to_vec()`), and this writes it
+    /// into a second in-memory buffer via the encoder — it does not stream,
+    /// so for a large proof both the raw and compressed copies are held at
+    /// once. Genuine streaming would mean encoding into `reqwest::Body`
+    /// directly instead of buffering the compressed output here.
+    fn compress_body(&self, bytes: &[u8]) -> (Vec<u8>, Option<&'static str>) {
+        if bytes.len() < self.compression.threshold_bytes {
+            return (bytes.to_vec(), None);
+        }
+
+        match self.compression.codec {
+            CompressionCodec::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::with_capacity(bytes.len()), Compression::default());
+                match encoder.write_all(bytes).and_then(|_| encoder.finish()) {
+                    Ok(compressed) => (compressed, Some("deflate")),
+                    Err(_) => (bytes.to_vec(), None),
+                }
+            }
+            CompressionCodec::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len()), Compression::default());
+                match encoder.write_all(bytes).and_then(|_| encoder.finish()) {
+                    Ok(compressed) => (compressed, Some("gzip")),
+                    Err(_) => (bytes.to_vec(), None),
+                }
+            }
+            CompressionCodec::None => (bytes.to_vec(), None),
+        }
+    }
+
+    /// `idempotent` controls what happens on an ambiguous-outcome error: one
+    /// where the request may already have reached and been processed by the
+    /// orchestrator (a timeout waiting on the response, a connection dropped
+    /// after the request went out, a failure while reading a response body,
+    /// or a 5xx/429 after the server read the body). Retrying those is safe
+    /// for a read like `get_proof_task`, but not for a write like
+    /// `submit_proof`, where it risks a duplicate submission with no
+    /// server-side idempotency key to de-dupe it. A connect failure that
+    /// never reached the server (it failed before any bytes were sent) is
+    /// always safe to retry regardless of `idempotent`.
     async fn make_request<T, U>(
         &self,
         url: &str,
         method: &str,
         request_data: &T,
-    ) -> Result<Option<U>, Box<dyn std::error::Error>>
+        node_id: &str,
+        idempotent: bool,
+    ) -> Result<Option<U>, OrchestratorError>
     where
         T: Message,
         U: Message + Default,
     {
         let request_bytes = request_data.encode_to_vec();
-        let url = format!("{}{}", self.base_url, url);
-
-        let response = match method {
-            "POST" => self.client.post(&url)
-                .header("Content-Type", "application/octet-stream")
-                .body(request_bytes)
-                .send()
-                .await,
-            "GET" => self.client.get(&url).send().await,
-            _ => return Err("[METHOD] Unsupported HTTP method".into()),
-        };
+        let start = Instant::now();
+        let mut consecutive_host_failures = 0u32;
+        let mut last_err: Option<OrchestratorError> = None;
+        let mut retried_after_auth_refresh = false;
 
-        let friendly_messages = match response {
-            Ok(resp) => resp,
-            Err(_) => return Err("[CONNECTION] Unable to reach server.".into()),
-        };
+        for attempt in 0..=self.backoff.max_retries {
+            if attempt > 0 {
+                if start.elapsed() >= self.backoff.max_elapsed {
+                    break;
+                }
+                tokio::time::sleep(self.backoff.delay_for_attempt(attempt - 1)).await;
+            }
 
-        if !friendly_messages.status().is_success() {
-            let status = friendly_messages.status();
-            let error_text = friendly_messages.text().await?;
+            let full_url = format!("{}{}", self.base_url(), url);
+            let token = self.auth.token(&self.client, self.base_url(), node_id).await;
+            let response = match method {
+                "POST" => {
+                    let (body, content_encoding) = self.compress_body(&request_bytes);
+                    let mut req = self
+                        .client
+                        .post(&full_url)
+                        .header("Content-Type", "application/octet-stream")
+                        .header("Accept-Encoding", "deflate, gzip")
+                        .body(body);
+                    if let Some(encoding) = content_encoding {
+                        req = req.header("Content-Encoding", encoding);
+                    }
+                    if let Some(token) = &token {
+                        req = req.bearer_auth(token);
+                    }
+                    req.send().await
+                }
+                "GET" => {
+                    let mut req = self.client.get(&full_url);
+                    if let Some(token) = &token {
+                        req = req.bearer_auth(token);
+                    }
+                    req.send().await
+                }
+                _ => unreachable!("make_request only supports GET and POST"),
+            };
 
-            let clean_error = if error_text.contains("<html>") {
-                format!("HTTP {}", status.as_u16())
-            } else {
-                error_text
+            let resp = match response {
+                Ok(resp) => resp,
+                Err(e) => {
+                    let err = if e.is_timeout() {
+                        OrchestratorError::Timeout
+                    } else {
+                        OrchestratorError::Connection
+                    };
+                    consecutive_host_failures += 1;
+                    if consecutive_host_failures >= self.backoff.failures_before_rotate {
+                        self.rotate_host();
+                        consecutive_host_failures = 0;
+                    }
+                    // A connect failure (including a connect timeout) never
+                    // reached the server, so it's always safe to retry.
+                    // Anything else — a timeout waiting on the response or a
+                    // connection dropped mid-request — is ambiguous: the
+                    // server may have already processed the write, so only
+                    // retry it when the caller says doing so is safe.
+                    if !e.is_connect() && !idempotent {
+                        return Err(err);
+                    }
+                    last_err = Some(err);
+                    continue;
+                }
             };
 
-            return Err(format!("[{}] Unexpected error: {}", status, clean_error).into());
-        }
+            if !resp.status().is_success() {
+                let status = resp.status().as_u16();
+                let retry_after = status_retry_after(&resp);
+                let error_text = resp
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| String::from("<unreadable body>"));
+                let err = OrchestratorError::Http {
+                    status,
+                    body: ApiError::parse(status, &error_text),
+                };
 
-        let response_bytes = friendly_messages.bytes().await?;
-        if response_bytes.is_empty() {
-            return Ok(None);
-        }
+                if status == 401 && !retried_after_auth_refresh {
+                    retried_after_auth_refresh = true;
+                    let _ = self.auth.refresh(&self.client, self.base_url(), node_id).await;
+                    last_err = Some(err);
+                    continue;
+                }
+
+                // The server has already read (and may have acted on) the
+                // request body by the time it answers with a status code, so
+                // a retryable 5xx/429 here is exactly as ambiguous for a
+                // write as a timeout is above.
+                if err.is_retryable() && idempotent {
+                    consecutive_host_failures += 1;
+                    if consecutive_host_failures >= self.backoff.failures_before_rotate {
+                        self.rotate_host();
+                        consecutive_host_failures = 0;
+                    }
+                    if let Some(retry_after) = retry_after {
+                        tokio::time::sleep(retry_after).await;
+                    }
+                    last_err = Some(err);
+                    continue;
+                }
+
+                return Err(err);
+            }
+
+            let content_encoding = resp
+                .headers()
+                .get(reqwest::header::CONTENT_ENCODING)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
 
-        match U::decode(response_bytes) {
-            Ok(msg) => Ok(Some(msg)),
-            Err(_) => Ok(None),
+            let response_bytes = match resp.bytes().await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    // A connection reset or timeout mid-body-read is exactly
+                    // as transient as the same failure on `.send()` above —
+                    // classify it the same way instead of treating every
+                    // body-read failure as a permanent decode error. The
+                    // server has already answered with a success status, so
+                    // a write has been processed and is never retried.
+                    let err = if e.is_timeout() {
+                        OrchestratorError::Timeout
+                    } else {
+                        OrchestratorError::Connection
+                    };
+                    consecutive_host_failures += 1;
+                    if consecutive_host_failures >= self.backoff.failures_before_rotate {
+                        self.rotate_host();
+                        consecutive_host_failures = 0;
+                    }
+                    if !idempotent {
+                        return Err(err);
+                    }
+                    last_err = Some(err);
+                    continue;
+                }
+            };
+            if response_bytes.is_empty() {
+                return Ok(None);
+            }
+
+            // This client advertises `Accept-Encoding` above but can't
+            // assume reqwest's own gzip/deflate feature flags are enabled
+            // (there's no Cargo.toml in this tree to confirm), so a
+            // compressed response is decompressed by hand rather than
+            // silently handed to `U::decode` as still-compressed bytes.
+            let response_bytes = match decompress_body(content_encoding.as_deref(), &response_bytes) {
+                Ok(bytes) => bytes,
+                Err(_) => return Ok(None),
+            };
+
+            return match U::decode(response_bytes.as_slice()) {
+                Ok(msg) => Ok(Some(msg)),
+                Err(_) => Ok(None),
+            };
         }
+
+        Err(last_err.unwrap_or(OrchestratorError::Connection))
     }
 
     pub async fn get_proof_task(
         &self,
         node_id: &str,
-    ) -> Result<GetProofTaskResponse, Box<dyn std::error::Error>> {
+    ) -> Result<GetProofTaskResponse, OrchestratorError> {
         let request = GetProofTaskRequest {
             node_id: node_id.to_string(),
             node_type: NodeType::CliProver as i32,
         };
 
+        // Fetching a task has no side effect on the orchestrator, so it's
+        // always safe to retry.
         let response = self
-            .make_request("/tasks", "POST", &request)
+            .make_request("/tasks", "POST", &request, node_id, true)
             .await?
-            .ok_or("No response received from get_proof_task")?;
+            .ok_or(OrchestratorError::EmptyResponse)?;
 
         Ok(response)
     }
 
+    /// Exchanges `node_id` for a fresh bearer token and caches it on disk.
+    pub async fn login(&self, node_id: &str) -> Result<(), OrchestratorError> {
+        self.auth
+            .refresh(&self.client, self.base_url(), node_id)
+            .await
+            .map(|_| ())
+    }
+
+    /// Deletes the cached auth ticket file. The token this client already
+    /// holds in memory is not cleared and is still sent on later requests.
+    pub fn logout(&self) -> io::Result<()> {
+        AuthManager::logout()
+    }
+
     pub async fn submit_proof(
         &self,
         node_id: &str,
         proof_hash: &str,
         proof: Vec<u8>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), OrchestratorError> {
+        let queued = QueuedProof {
+            proof_hash: proof_hash.to_string(),
+            node_id: node_id.to_string(),
+            proof,
+            status: ProofStatus::Pending,
+            bandwidth_bytes_per_sec: None,
+        };
+        // Record the proof before attempting the POST so it isn't lost if the
+        // process crashes or the request fails; the drain task retries it.
+        self.queue.enqueue_pending(&queued);
+
+        self.submit_queued(&queued).await
+    }
+
+    /// Submits an already-queued proof and marks it `Submitted` on success.
+    /// Left `Pending` on failure so the drain task will retry it later.
+    ///
+    /// Claims `proof_hash` in the in-memory guard before POSTing so a
+    /// concurrent drain pass (or a second foreground call racing the first)
+    /// can't submit the same proof twice while this call is still in flight.
+    /// If the claim is already held, the proof is left for whoever holds it
+    /// to finish — it's still durably queued either way.
+    async fn submit_queued(&self, queued: &QueuedProof) -> Result<(), OrchestratorError> {
+        if !self.queue.try_claim(&queued.proof_hash) {
+            return Ok(());
+        }
+        let result = self.submit_claimed(queued).await;
+        self.queue.release_claim(&queued.proof_hash);
+        result
+    }
+
+    async fn submit_claimed(&self, queued: &QueuedProof) -> Result<(), OrchestratorError> {
+        /// Printed once per process, not once per proof, so a long-running
+        /// prover doesn't spam stderr every time it measures bandwidth.
+        static BANDWIDTH_NOT_WIRED_WARNING: std::sync::Once = std::sync::Once::new();
+
         let (program_memory, total_memory) = get_memory_info();
         let flops = measure_flops();
+        let location = self.geo.resolve(&self.client).await;
+
+        // Measure upload throughput at most once per proof: it's recorded on
+        // the queue entry and surfaced via `queued_proofs()`, and skipping it
+        // on retries avoids spending bandwidth on the constrained uplinks
+        // this feature exists for every time the drain loop wakes up.
+        //
+        // KNOWN GAP, not a finished feature: `NodeTelemetry` has no bandwidth
+        // field upstream, so this measurement only ever reaches the local
+        // queue and the warning below — it is never sent to the
+        // orchestrator, so it does not yet fix the orchestrator's node map.
+        // Landing that requires a proto change in the shared `nexus_orchestrator`
+        // definitions, which is outside this client crate.
+        if queued.bandwidth_bytes_per_sec.is_none() {
+            if let Some(measurement_url) = &self.telemetry.bandwidth_measurement_url {
+                if let Some(bandwidth) = telemetry::measure_upload_throughput(
+                    &self.client,
+                    measurement_url,
+                    self.telemetry.bandwidth_payload_bytes,
+                )
+                .await
+                {
+                    self.queue.record_bandwidth(&queued.proof_hash, bandwidth);
+                    BANDWIDTH_NOT_WIRED_WARNING.call_once(|| {
+                        eprintln!(
+                            "warning: measured upload bandwidth is recorded locally (see queued_proofs()) but is NOT sent to the orchestrator yet — NodeTelemetry has no bandwidth field upstream"
+                        );
+                    });
+                }
+            }
+        }
 
         let request = SubmitProofRequest {
-            node_id: node_id.to_string(),
+            node_id: queued.node_id.clone(),
             node_type: NodeType::CliProver as i32,
-            proof_hash: proof_hash.to_string(),
-            proof: proof.clone(),
+            proof_hash: queued.proof_hash.clone(),
+            proof: queued.proof.clone(),
             node_telemetry: Some(NodeTelemetry {
                 flops_per_sec: Some(flops as i32),
                 memory_used: Some(program_memory),
                 memory_capacity: Some(total_memory),
-                location: Some("US".to_string()),
+                location: Some(location),
             }),
         };
 
-        // Convert to JSON and save
-        let json_request = convert_to_json(&request);
-        if let Ok(json) = serde_json::to_string_pretty(&json_request) {
-            let _ = save_to_file("submit_proof.json", &json);
-        }
+        // Submitting a proof is a write the orchestrator may already have
+        // processed by the time a timeout or 5xx comes back, and there's no
+        // server-side idempotency key to de-dupe a second POST, so ambiguous
+        // errors are not retried here.
+        self.make_request::<SubmitProofRequest, ()>(
+            "/tasks/submit",
+            "POST",
+            &request,
+            &queued.node_id,
+            false,
+        )
+        .await?;
 
-        // Save binary payload
-        let _ = save_binary_to_file("submit_proof.bin", &proof);
+        self.queue.mark_submitted(&queued.proof_hash);
+        Ok(())
+    }
 
-        self.make_request::<SubmitProofRequest, ()>("/tasks/submit", "POST", &request)
-            .await?;
+    /// Lists every proof the durable queue knows about, regardless of status.
+    pub fn queued_proofs(&self) -> Vec<QueuedProof> {
+        self.queue.all()
+    }
 
-        Ok(())
+    /// `None` if the proof queue is durable. `Some` with a human-readable
+    /// reason if it fell back to a non-durable in-memory queue (e.g. the
+    /// directory was locked by another process, or couldn't be opened at
+    /// all) — callers that care about surviving a crash should check this
+    /// rather than relying on the warning printed to stderr.
+    pub fn queue_degraded_reason(&self) -> Option<String> {
+        self.queue.degraded_reason().map(str::to_string)
+    }
+
+    /// Spawns a background task that periodically retries proofs still
+    /// `Pending` in the durable queue (e.g. left over from a crash or a POST
+    /// that failed after all of `make_request`'s own retries were exhausted).
+    pub fn spawn_proof_drain(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut attempt = 0u32;
+            loop {
+                let pending = client.queue.pending();
+                if pending.is_empty() {
+                    attempt = 0;
+                } else {
+                    for queued in &pending {
+                        if client.submit_queued(queued).await.is_err() {
+                            attempt += 1;
+                        }
+                    }
+                }
+
+                let delay = client
+                    .backoff
+                    .delay_for_attempt(attempt.min(6))
+                    .max(Duration::from_secs(5));
+                tokio::time::sleep(delay).await;
+            }
+        })
     }
 }
 
-/// Converts `SubmitProofRequest` to a JSON-friendly struct
-fn convert_to_json(request: &SubmitProofRequest) -> SubmitProofRequestJson {
-    SubmitProofRequestJson {
-        node_id: request.node_id.clone(),
-        node_type: request.node_type,
-        proof_hash: request.proof_hash.clone(),
-        proof: base64::encode(&request.proof), // Encode binary data as Base64
-        node_telemetry: request.node_telemetry.as_ref().map(|t| NodeTelemetryJson {
-            flops_per_sec: t.flops_per_sec,
-            memory_used: t.memory_used,
-            memory_capacity: t.memory_capacity,
-            location: t.location.clone(),
-        }),
+/// Decompresses `bytes` per `content_encoding`, if it names a codec we know
+/// how to handle. Unrecognized or absent encodings are passed through as-is.
+fn decompress_body(content_encoding: Option<&str>, bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match content_encoding {
+        Some("gzip") => {
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut out)?;
+        }
+        Some("deflate") => {
+            flate2::read::DeflateDecoder::new(bytes).read_to_end(&mut out)?;
+        }
+        _ => return Ok(bytes.to_vec()),
     }
+    Ok(out)
 }
 
-/// Saves a string (JSON) to a file
-fn save_to_file(filename: &str, content: &str) -> io::Result<()> {
-    let mut file = File::create(filename)?;
-    file.write_all(content.as_bytes())?;
-    Ok(())
+/// Parses a `Retry-After` header (seconds form) off a response, if present.
+fn status_retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(Duration::from_secs)
 }
 
-/// Saves binary data to a file
-fn save_binary_to_file(filename: &str, data: &[u8]) -> io::Result<()> {
-    let mut file = File::create(filename)?;
-    file.write_all(data)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each attempt's delay must stay within the 0.5x-1.5x jitter band around
+    /// `base_delay * factor^attempt`, and attempt 3 must still be longer than
+    /// attempt 0 even at the jitter extremes (adjacent attempts can overlap).
+    #[test]
+    fn delay_for_attempt_grows_exponentially_within_jitter_bounds() {
+        let backoff = BackoffConfig {
+            base_delay: Duration::from_millis(500),
+            factor: 2.0,
+            max_retries: 5,
+            max_elapsed: Duration::from_secs(30),
+            failures_before_rotate: 2,
+        };
+
+        for attempt in 0..backoff.max_retries {
+            let expected_base = 500.0 * 2f64.powi(attempt as i32);
+            let min_ms = (expected_base * 0.5) as u64;
+            let max_ms = (expected_base * 1.5) as u64;
+
+            for _ in 0..20 {
+                let delay_ms = backoff.delay_for_attempt(attempt).as_millis() as u64;
+                assert!(
+                    delay_ms >= min_ms && delay_ms <= max_ms,
+                    "attempt {attempt}: delay {delay_ms}ms outside [{min_ms}, {max_ms}]"
+                );
+            }
+        }
+
+        // Even at the lowest jitter draw for a later attempt and the highest
+        // jitter draw for an earlier one, backoff should still trend upward.
+        let earliest_worst_case = 500.0 * 1.5;
+        let latest_best_case = 500.0 * 2f64.powi(3) * 0.5;
+        assert!(latest_best_case > earliest_worst_case);
+    }
 }
@@ -0,0 +1,223 @@
+use super::error::OrchestratorError;
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// A bearer token issued by the orchestrator, along with its expiry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthInfo {
+    pub token: String,
+    /// Unix timestamp (seconds) at which `token` stops being valid.
+    pub expires_at: u64,
+}
+
+impl AuthInfo {
+    /// True once fewer than 60 seconds remain before the token expires.
+    fn is_near_expiry(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.expires_at.saturating_sub(now) < 60
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+    expires_in: u64,
+}
+
+fn ticket_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("nexus")
+        .join("auth-ticket.json")
+}
+
+fn load_ticket() -> Option<AuthInfo> {
+    let bytes = std::fs::read(ticket_path()).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn save_ticket(info: &AuthInfo) -> io::Result<()> {
+    let path = ticket_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(info)?)
+}
+
+fn delete_ticket() -> io::Result<()> {
+    match std::fs::remove_file(ticket_path()) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Exchanges `node_id` for a fresh token and persists it to the ticket file.
+async fn login(client: &Client, base_url: &str, node_id: &str) -> Result<AuthInfo, OrchestratorError> {
+    let response = client
+        .post(format!("{}/auth/login", base_url))
+        .json(&serde_json::json!({ "node_id": node_id }))
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                OrchestratorError::Timeout
+            } else {
+                OrchestratorError::Connection
+            }
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap_or_default();
+        return Err(OrchestratorError::Http {
+            status,
+            body: super::error::ApiError::parse(status, &text),
+        });
+    }
+
+    let parsed: LoginResponse = response.json().await.map_err(|_| OrchestratorError::Decode)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let info = AuthInfo {
+        token: parsed.token,
+        expires_at: now + parsed.expires_in,
+    };
+    let _ = save_ticket(&info);
+    Ok(info)
+}
+
+enum AuthState {
+    Idle(Option<AuthInfo>),
+    Refreshing(Shared<BoxFuture<'static, Result<AuthInfo, OrchestratorError>>>),
+}
+
+/// Holds the current auth ticket and single-flights refreshes so that
+/// concurrent requests hitting an expired token don't all re-authenticate.
+pub struct AuthManager {
+    state: Mutex<AuthState>,
+}
+
+impl AuthManager {
+    pub fn load() -> Self {
+        Self {
+            state: Mutex::new(AuthState::Idle(load_ticket())),
+        }
+    }
+
+    /// Returns a usable bearer token, refreshing first if it's missing or
+    /// near expiry. If another caller's refresh is already in flight, awaits
+    /// that same future instead of returning tokenless.
+    pub async fn token(&self, client: &Client, base_url: &str, node_id: &str) -> Option<String> {
+        enum Action {
+            UseExisting(Option<AuthInfo>),
+            NeedsRefresh,
+            AwaitInFlight(Shared<BoxFuture<'static, Result<AuthInfo, OrchestratorError>>>),
+        }
+
+        let action = match &*self.state.lock().await {
+            AuthState::Idle(Some(info)) if !info.is_near_expiry() => Action::UseExisting(Some(info.clone())),
+            AuthState::Idle(_) => Action::NeedsRefresh,
+            AuthState::Refreshing(fut) => Action::AwaitInFlight(fut.clone()),
+        };
+
+        match action {
+            Action::UseExisting(info) => info.map(|i| i.token),
+            Action::NeedsRefresh => self.refresh(client, base_url, node_id).await.ok().map(|i| i.token),
+            Action::AwaitInFlight(fut) => fut.await.ok().map(|i| i.token),
+        }
+    }
+
+    /// Forces a refresh. If one is already in flight, waits on it instead of
+    /// starting a second one, so every caller sees the same result.
+    pub async fn refresh(
+        &self,
+        client: &Client,
+        base_url: &str,
+        node_id: &str,
+    ) -> Result<AuthInfo, OrchestratorError> {
+        let in_flight = {
+            let mut state = self.state.lock().await;
+            match &*state {
+                AuthState::Refreshing(fut) => fut.clone(),
+                AuthState::Idle(_) => {
+                    let client = client.clone();
+                    let base_url = base_url.to_string();
+                    let node_id = node_id.to_string();
+                    let fut: BoxFuture<'static, Result<AuthInfo, OrchestratorError>> =
+                        Box::pin(async move { login(&client, &base_url, &node_id).await });
+                    let shared = fut.shared();
+                    *state = AuthState::Refreshing(shared.clone());
+                    shared
+                }
+            }
+        };
+
+        let result = in_flight.await;
+        let mut state = self.state.lock().await;
+        if let AuthState::Refreshing(_) = &*state {
+            *state = AuthState::Idle(result.clone().ok());
+        }
+        result
+    }
+
+    /// Deletes the on-disk ticket. The in-memory token of an existing
+    /// `AuthManager` is not cleared.
+    pub fn logout() -> io::Result<()> {
+        delete_ticket()
+    }
+
+    #[cfg(test)]
+    fn with_state(state: AuthState) -> Self {
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Two callers that see a refresh already in flight must both get the
+    /// refreshed token, and the refresh itself must only run once.
+    #[tokio::test]
+    async fn concurrent_token_calls_share_in_flight_refresh() {
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&call_count);
+
+        let fut: BoxFuture<'static, Result<AuthInfo, OrchestratorError>> = Box::pin(async move {
+            counted.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            Ok(AuthInfo {
+                token: "fresh-token".to_string(),
+                expires_at: u64::MAX,
+            })
+        });
+
+        let manager = Arc::new(AuthManager::with_state(AuthState::Refreshing(fut.shared())));
+        let client = Client::new();
+
+        let (a, b) = tokio::join!(
+            manager.token(&client, "http://unused.invalid", "node-a"),
+            manager.token(&client, "http://unused.invalid", "node-a"),
+        );
+
+        assert_eq!(a.as_deref(), Some("fresh-token"));
+        assert_eq!(b.as_deref(), Some("fresh-token"));
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,84 @@
+use serde::Deserialize;
+use std::fmt;
+
+/// Error body returned by the orchestrator, when it returns one.
+///
+/// Falls back to the raw response text (or `HTTP {status}` for HTML error
+/// pages) when the body isn't valid JSON.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiError {
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+impl ApiError {
+    /// Parses `text` as a JSON `ApiError`, falling back to a plain message.
+    pub fn parse(status: u16, text: &str) -> Self {
+        if let Ok(api_error) = serde_json::from_str::<ApiError>(text) {
+            return api_error;
+        }
+
+        let message = if text.contains("<html>") {
+            format!("HTTP {}", status)
+        } else {
+            text.to_string()
+        };
+
+        Self {
+            message,
+            code: None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "{} ({})", self.message, code),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Errors produced while talking to the orchestrator.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum OrchestratorError {
+    /// The orchestrator returned a non-2xx response.
+    #[error("[{status}] {body}")]
+    Http { status: u16, body: ApiError },
+
+    /// A response body could not be decoded. Only a login response's JSON
+    /// body is reported this way; a protobuf response that fails to
+    /// decompress or decode is treated by `make_request` as no message.
+    #[error("Failed to decode orchestrator response")]
+    Decode,
+
+    /// The connection to the orchestrator could not be established, or failed
+    /// before a complete response was received.
+    #[error("[CONNECTION] Unable to reach server.")]
+    Connection,
+
+    /// The request did not complete within the configured timeout.
+    #[error("[TIMEOUT] Request to orchestrator timed out.")]
+    Timeout,
+
+    /// The orchestrator returned no usable message where one was expected: an
+    /// empty body, or one that could not be decompressed or decoded.
+    #[error("No response received from orchestrator")]
+    EmptyResponse,
+}
+
+impl OrchestratorError {
+    /// Whether this error represents a transient condition worth retrying.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OrchestratorError::Http { status, .. } => {
+                *status >= 500 || *status == 429
+            }
+            OrchestratorError::Connection | OrchestratorError::Timeout => true,
+            OrchestratorError::Decode | OrchestratorError::EmptyResponse => false,
+        }
+    }
+}
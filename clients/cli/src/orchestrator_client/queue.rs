@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Status of a proof tracked in the durable queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofStatus {
+    /// Generated and recorded, but not yet acknowledged by the orchestrator.
+    Pending,
+    /// Successfully submitted.
+    Submitted,
+}
+
+/// A proof awaiting submission, persisted so completed work survives a crash
+/// or a failed POST. Of the telemetry sent with it, only the bandwidth
+/// measurement is stored; flops, memory and location are measured again on
+/// each submission attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedProof {
+    pub proof_hash: String,
+    pub node_id: String,
+    pub proof: Vec<u8>,
+    pub status: ProofStatus,
+    /// Upload throughput observed while submitting this proof, if bandwidth
+    /// measurement is enabled. Recorded once and reused on retries so the
+    /// drain loop doesn't re-measure (and re-spend bandwidth on) a proof it
+    /// already measured.
+    #[serde(default)]
+    pub bandwidth_bytes_per_sec: Option<u64>,
+}
+
+/// Default location for the queue's `sled` tree, alongside the auth ticket.
+pub fn default_queue_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("nexus")
+        .join("proof-queue")
+}
+
+/// `sled` takes an exclusive lock on its directory, so a second instance of
+/// the queue at the same path (e.g. a second prover process) can't open it.
+/// That instance falls back to this non-durable in-memory backend instead of
+/// refusing to start; it still de-dupes in-flight submissions correctly, it
+/// just won't survive a restart.
+enum Backend {
+    Sled(sled::Db),
+    InMemory(Mutex<HashMap<String, QueuedProof>>),
+}
+
+/// At-least-once queue for proofs awaiting submission, plus an in-memory
+/// guard that stops the background drain from resubmitting a proof a
+/// foreground call already has in flight (and vice versa).
+///
+/// Entries are written as `Pending` before the submit POST is attempted, and
+/// flipped to `Submitted` only once the orchestrator has acknowledged them.
+/// Anything still `Pending` on restart gets retried by the drain loop.
+pub struct ProofQueue {
+    backend: Backend,
+    in_flight: Mutex<HashSet<String>>,
+    /// `Some(reason)` if [`ProofQueue::open`] couldn't get a durable `sled`
+    /// tree and fell back to the non-durable in-memory backend.
+    degraded_reason: Option<String>,
+}
+
+/// Best-effort check for whether a `sled::open` failure is the expected
+/// "another process already holds this queue's directory lock" case, as
+/// opposed to something that deserves louder attention (disk full,
+/// permissions, a corrupted tree). `sled` surfaces lock contention as a plain
+/// `io::Error`, not its own error variant, so this keys off the `io::Error`
+/// kind rather than the `sled::Error` shape.
+fn is_lock_contention(err: &sled::Error) -> bool {
+    matches!(
+        err,
+        sled::Error::Io(io_err) if io_err.kind() == std::io::ErrorKind::WouldBlock
+    )
+}
+
+impl ProofQueue {
+    /// Opens the durable queue at `path`, falling back to an in-memory queue
+    /// if it can't be opened. Lock contention (a second prover process
+    /// pointed at the same path) is the expected case and logged quietly;
+    /// anything else (full disk, permissions, a corrupted tree) is logged
+    /// loudly, since it means durability is being lost for a reason the
+    /// operator should actually look into. Either way the degraded state is
+    /// never silent — callers can check [`ProofQueue::degraded_reason`]
+    /// instead of having to watch stderr.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        match sled::open(path.as_ref()) {
+            Ok(tree) => Self {
+                backend: Backend::Sled(tree),
+                in_flight: Mutex::new(HashSet::new()),
+                degraded_reason: None,
+            },
+            Err(e) if is_lock_contention(&e) => {
+                eprintln!(
+                    "warning: proof queue at {:?} is locked by another process; falling back to a non-durable in-memory queue for this run",
+                    path.as_ref()
+                );
+                Self {
+                    backend: Backend::InMemory(Mutex::new(HashMap::new())),
+                    in_flight: Mutex::new(HashSet::new()),
+                    degraded_reason: Some(format!("queue directory locked by another process: {e}")),
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "error: failed to open durable proof queue at {:?} ({e}); falling back to a non-durable in-memory queue — queued proofs will NOT survive a crash or restart",
+                    path.as_ref()
+                );
+                Self {
+                    backend: Backend::InMemory(Mutex::new(HashMap::new())),
+                    in_flight: Mutex::new(HashSet::new()),
+                    degraded_reason: Some(format!("failed to open queue: {e}")),
+                }
+            }
+        }
+    }
+
+    /// `None` if the queue is backed by durable on-disk storage. `Some` with
+    /// a human-readable reason if [`ProofQueue::open`] fell back to the
+    /// non-durable in-memory backend, so callers (and `OrchestratorClient`'s
+    /// own callers) have a way to notice lost durability beyond stderr.
+    pub fn degraded_reason(&self) -> Option<&str> {
+        self.degraded_reason.as_deref()
+    }
+
+    pub fn enqueue_pending(&self, proof: &QueuedProof) {
+        match &self.backend {
+            Backend::Sled(tree) => {
+                let bytes = serde_json::to_vec(proof).expect("QueuedProof is always serializable");
+                let _ = tree.insert(proof.proof_hash.as_bytes(), bytes);
+                let _ = tree.flush();
+            }
+            Backend::InMemory(map) => {
+                map.lock()
+                    .unwrap()
+                    .insert(proof.proof_hash.clone(), proof.clone());
+            }
+        }
+    }
+
+    pub fn mark_submitted(&self, proof_hash: &str) {
+        match &self.backend {
+            Backend::Sled(tree) => {
+                if let Ok(Some(bytes)) = tree.get(proof_hash.as_bytes()) {
+                    if let Ok(mut proof) = serde_json::from_slice::<QueuedProof>(&bytes) {
+                        proof.status = ProofStatus::Submitted;
+                        let bytes = serde_json::to_vec(&proof).expect("QueuedProof is always serializable");
+                        let _ = tree.insert(proof_hash.as_bytes(), bytes);
+                        let _ = tree.flush();
+                    }
+                }
+            }
+            Backend::InMemory(map) => {
+                if let Some(proof) = map.lock().unwrap().get_mut(proof_hash) {
+                    proof.status = ProofStatus::Submitted;
+                }
+            }
+        }
+    }
+
+    /// Records an observed upload throughput for `proof_hash`, so it's
+    /// visible via [`ProofQueue::all`] and isn't re-measured on retry.
+    pub fn record_bandwidth(&self, proof_hash: &str, bandwidth_bytes_per_sec: u64) {
+        match &self.backend {
+            Backend::Sled(tree) => {
+                if let Ok(Some(bytes)) = tree.get(proof_hash.as_bytes()) {
+                    if let Ok(mut proof) = serde_json::from_slice::<QueuedProof>(&bytes) {
+                        proof.bandwidth_bytes_per_sec = Some(bandwidth_bytes_per_sec);
+                        let bytes = serde_json::to_vec(&proof).expect("QueuedProof is always serializable");
+                        let _ = tree.insert(proof_hash.as_bytes(), bytes);
+                        let _ = tree.flush();
+                    }
+                }
+            }
+            Backend::InMemory(map) => {
+                if let Some(proof) = map.lock().unwrap().get_mut(proof_hash) {
+                    proof.bandwidth_bytes_per_sec = Some(bandwidth_bytes_per_sec);
+                }
+            }
+        }
+    }
+
+    /// All proofs still awaiting a successful submission.
+    pub fn pending(&self) -> Vec<QueuedProof> {
+        self.all()
+            .into_iter()
+            .filter(|p| p.status == ProofStatus::Pending)
+            .collect()
+    }
+
+    /// Every proof known to the queue, regardless of status.
+    pub fn all(&self) -> Vec<QueuedProof> {
+        match &self.backend {
+            Backend::Sled(tree) => tree
+                .iter()
+                .values()
+                .filter_map(|v| v.ok())
+                .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+                .collect(),
+            Backend::InMemory(map) => map.lock().unwrap().values().cloned().collect(),
+        }
+    }
+
+    /// Attempts to claim exclusive ownership of submitting `proof_hash`.
+    /// Returns `false` if a submission for it (foreground or drain) is
+    /// already in flight, so the caller can skip and avoid a duplicate POST.
+    pub fn try_claim(&self, proof_hash: &str) -> bool {
+        self.in_flight.lock().unwrap().insert(proof_hash.to_string())
+    }
+
+    /// Releases a claim taken by [`ProofQueue::try_claim`].
+    pub fn release_claim(&self, proof_hash: &str) {
+        self.in_flight.lock().unwrap().remove(proof_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_claim_is_exclusive_until_released() {
+        let queue = ProofQueue::open(std::env::temp_dir().join(format!(
+            "nexus-proof-queue-test-{}",
+            std::process::id()
+        )));
+
+        assert!(queue.try_claim("hash-a"));
+        // A second claim attempt (e.g. the drain loop) must not succeed
+        // while the first submission is still in flight.
+        assert!(!queue.try_claim("hash-a"));
+
+        queue.release_claim("hash-a");
+        assert!(queue.try_claim("hash-a"));
+    }
+
+    #[test]
+    fn falls_back_to_in_memory_when_directory_is_locked() {
+        let path = std::env::temp_dir().join(format!(
+            "nexus-proof-queue-locked-{}",
+            std::process::id()
+        ));
+        let _first = ProofQueue::open(&path);
+        // Opening the same sled directory twice in one process can't
+        // succeed (sled takes an exclusive lock); this must fall back to an
+        // in-memory queue instead of panicking.
+        let second = ProofQueue::open(&path);
+
+        // The fallback must be visible through the public API, not just stderr.
+        assert!(second.degraded_reason().is_some());
+
+        let proof = QueuedProof {
+            proof_hash: "hash-b".to_string(),
+            node_id: "node".to_string(),
+            proof: vec![1, 2, 3],
+            status: ProofStatus::Pending,
+            bandwidth_bytes_per_sec: None,
+        };
+        second.enqueue_pending(&proof);
+        assert_eq!(second.pending().len(), 1);
+    }
+
+    #[test]
+    fn durable_queue_reports_no_degradation() {
+        let path = std::env::temp_dir().join(format!(
+            "nexus-proof-queue-durable-{}",
+            std::process::id()
+        ));
+        let queue = ProofQueue::open(&path);
+        assert_eq!(queue.degraded_reason(), None);
+    }
+}
@@ -0,0 +1,83 @@
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Instant;
+use tokio::sync::OnceCell;
+
+/// Where to source the node's location, and whether to measure upload
+/// throughput before each submission.
+#[derive(Clone, Default)]
+pub struct TelemetryConfig {
+    /// Skips the geo-IP lookup entirely and reports this value instead.
+    pub location_override: Option<String>,
+    /// Endpoint to POST a throwaway payload to when measuring upload speed.
+    /// Bandwidth is only measured when this is set.
+    pub bandwidth_measurement_url: Option<String>,
+    /// Size of the throwaway payload used for the throughput measurement.
+    pub bandwidth_payload_bytes: usize,
+}
+
+impl TelemetryConfig {
+    pub fn new() -> Self {
+        Self {
+            location_override: None,
+            bandwidth_measurement_url: None,
+            bandwidth_payload_bytes: 256 * 1024,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GeoIpResponse {
+    country_code: Option<String>,
+}
+
+/// Resolves the node's country on the first call to [`GeoLocator::resolve`]
+/// (not at startup) and caches it, so privacy conscious operators only pay
+/// the lookup (or skip it via an override) a single time per process.
+pub struct GeoLocator {
+    location_override: Option<String>,
+    cached: OnceCell<String>,
+}
+
+impl GeoLocator {
+    pub fn new(location_override: Option<String>) -> Self {
+        Self {
+            location_override,
+            cached: OnceCell::new(),
+        }
+    }
+
+    pub async fn resolve(&self, client: &Client) -> String {
+        if let Some(location) = &self.location_override {
+            return location.clone();
+        }
+
+        self.cached
+            .get_or_init(|| async { lookup_country(client).await.unwrap_or_else(|| "unknown".to_string()) })
+            .await
+            .clone()
+    }
+}
+
+async fn lookup_country(client: &Client) -> Option<String> {
+    let response = client.get("https://ipapi.co/json/").send().await.ok()?;
+    let parsed: GeoIpResponse = response.json().await.ok()?;
+    parsed.country_code
+}
+
+/// Times a throwaway upload of `payload_bytes` to `url` and returns the
+/// observed throughput in bytes/sec, analogous to a single-sample upload
+/// speedtest. Returns `None` if the measurement endpoint is unreachable.
+pub async fn measure_upload_throughput(client: &Client, url: &str, payload_bytes: usize) -> Option<u64> {
+    let payload = vec![0u8; payload_bytes];
+    let start = Instant::now();
+    let response = client.post(url).body(payload).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    if elapsed <= 0.0 {
+        return None;
+    }
+    Some((payload_bytes as f64 / elapsed) as u64)
+}